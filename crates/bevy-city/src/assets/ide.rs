@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use bevy::{
+    app::prelude::*,
+    asset::{AddAsset, AssetLoader, LoadedAsset},
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+
+/// One `objs` row from an `.ide`: everything the game knows about a model
+/// before it's ever placed by an IPL `Instance`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectDef {
+    pub model_name: String,
+    pub texture_dictionary: String,
+    pub draw_distance: f32,
+    pub flags: u32,
+}
+
+#[derive(Debug, TypeUuid, PartialEq)]
+#[uuid = "6a55a6b1-7f9a-4d2d-9e7a-3b6f0a8a9c21"]
+pub struct Ide {
+    pub objects: Vec<ObjectDef>,
+}
+
+impl Ide {
+    pub fn parse(data: &str) -> Self {
+        let sections = crate::maps::ipl_parser::categorise_lines(data);
+
+        let objects = sections
+            .get("objs")
+            .map(|lines| {
+                lines
+                    .iter()
+                    .filter_map(|line| {
+                        let segments: Vec<_> = crate::maps::ipl_parser::split_line(line);
+                        // id, modelname, txdname, [meshcount,] drawdist, flags
+                        if segments.len() < 5 {
+                            return None;
+                        }
+
+                        let flags = segments[segments.len() - 1].parse().ok()?;
+                        let draw_distance = segments[segments.len() - 2].parse().ok()?;
+
+                        Some(ObjectDef {
+                            model_name: segments[1].to_string(),
+                            texture_dictionary: segments[2].to_string(),
+                            draw_distance,
+                            flags,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ide { objects }
+    }
+}
+
+/// Maps a lowercased model name to its `.ide` definition, built up as `.ide`
+/// assets finish loading. Streaming looks each object's draw distance up
+/// here instead of hard-coding it; the stored object flags are not read by
+/// any spawning code.
+#[derive(Default)]
+pub struct IdeRegistry(HashMap<String, ObjectDef>);
+
+impl IdeRegistry {
+    pub fn get(&self, model_name: &str) -> Option<&ObjectDef> {
+        self.0.get(&model_name.to_lowercase())
+    }
+
+    fn insert_all(&mut self, ide: &Ide) {
+        for object in &ide.objects {
+            self.0.insert(object.model_name.to_lowercase(), object.clone());
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct IdeLoader;
+
+impl AssetLoader for IdeLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut bevy::asset::LoadContext,
+    ) -> BoxedFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let value = Ide::parse(std::str::from_utf8(bytes)?);
+            load_context.set_default_asset(LoadedAsset::new(value));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        static EXTENSIONS: &[&str] = &["ide"];
+        EXTENSIONS
+    }
+}
+
+/// Feeds newly-loaded `.ide` definitions into the `IdeRegistry` so spawning
+/// code can look model metadata up by name.
+fn index_loaded_ides(
+    mut ev_asset: EventReader<AssetEvent<Ide>>,
+    assets: Res<Assets<Ide>>,
+    mut registry: ResMut<IdeRegistry>,
+) {
+    for ev in ev_asset.iter() {
+        if let AssetEvent::Created { handle } = ev {
+            registry.insert_all(assets.get(handle).unwrap());
+        }
+    }
+}
+
+/// Adds support for `.ide` file loading, plus the registry streaming reads
+/// draw distance from.
+#[derive(Default)]
+pub struct IdePlugin;
+impl Plugin for IdePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Ide>()
+            .init_asset_loader::<IdeLoader>()
+            .init_resource::<IdeRegistry>()
+            .add_system(index_loaded_ides);
+    }
+}
+
+mod tests {
+    pub use super::*;
+
+    #[test]
+    fn can_parse_objs_section() {
+        const TEST_DATA: &str = r"
+objs
+1700, docktrack, docks, 1, 299, 0
+1701, dockwarehouse, docks, 1, 500, 4
+end
+";
+        let ide = Ide::parse(TEST_DATA.trim());
+        assert_eq!(
+            ide,
+            Ide {
+                objects: vec![
+                    ObjectDef {
+                        model_name: "docktrack".to_string(),
+                        texture_dictionary: "docks".to_string(),
+                        draw_distance: 299.0,
+                        flags: 0,
+                    },
+                    ObjectDef {
+                        model_name: "dockwarehouse".to_string(),
+                        texture_dictionary: "docks".to_string(),
+                        draw_distance: 500.0,
+                        flags: 4,
+                    },
+                ],
+            }
+        );
+    }
+}
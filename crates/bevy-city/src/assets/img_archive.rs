@@ -0,0 +1,115 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use bevy::asset::{AssetIo, AssetIoError, BoxedFuture, Metadata};
+
+/// One `.dir` entry: the sector offset and size of a file packed inside the
+/// matching `.img` archive.
+#[derive(Debug, Clone, Copy)]
+struct DirEntry {
+    sector_offset: u32,
+    sector_count: u16,
+}
+
+const SECTOR_SIZE: u64 = 2048;
+const DIR_ENTRY_SIZE: usize = 32;
+
+/// An `AssetIo` that serves files out of a GTA `.img`/`.dir` archive pair,
+/// falling back to the real filesystem for anything the archive doesn't
+/// contain. This lets `asset_server.load("foo.dff")` work whether `foo.dff`
+/// was extracted to disk or is still packed inside `models/gta3.img`.
+pub struct ImgArchiveIo {
+    img_path: PathBuf,
+    entries: HashMap<String, DirEntry>,
+    fallback: Box<dyn AssetIo>,
+}
+
+impl ImgArchiveIo {
+    pub fn new(dir_path: impl AsRef<Path>, fallback: Box<dyn AssetIo>) -> std::io::Result<Self> {
+        let dir_path = dir_path.as_ref();
+        let dir_bytes = std::fs::read(dir_path)?;
+        let entries = Self::parse_dir(&dir_bytes);
+
+        Ok(Self {
+            img_path: dir_path.with_extension("img"),
+            entries,
+            fallback,
+        })
+    }
+
+    fn parse_dir(dir_bytes: &[u8]) -> HashMap<String, DirEntry> {
+        dir_bytes
+            .chunks_exact(DIR_ENTRY_SIZE)
+            .map(|entry| {
+                let sector_offset = u32::from_le_bytes(entry[0..4].try_into().unwrap());
+                let sector_count = u16::from_le_bytes(entry[4..6].try_into().unwrap());
+                let name_bytes = &entry[8..32];
+                let name = String::from_utf8_lossy(name_bytes)
+                    .trim_end_matches('\0')
+                    .to_lowercase();
+
+                (
+                    name,
+                    DirEntry {
+                        sector_offset,
+                        sector_count,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn entry_for(&self, path: &Path) -> Option<DirEntry> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+        self.entries.get(&name).copied()
+    }
+
+    fn read_entry(&self, entry: DirEntry) -> Result<Vec<u8>, AssetIoError> {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let mut file = std::fs::File::open(&self.img_path)
+            .map_err(|_| AssetIoError::NotFound(self.img_path.clone()))?;
+        file.seek(SeekFrom::Start(entry.sector_offset as u64 * SECTOR_SIZE))
+            .map_err(AssetIoError::Io)?;
+
+        let mut bytes = vec![0u8; entry.sector_count as usize * SECTOR_SIZE as usize];
+        file.read_exact(&mut bytes).map_err(AssetIoError::Io)?;
+        Ok(bytes)
+    }
+}
+
+impl AssetIo for ImgArchiveIo {
+    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        Box::pin(async move {
+            match self.entry_for(path) {
+                Some(entry) => self.read_entry(entry),
+                None => self.fallback.load_path(path).await,
+            }
+        })
+    }
+
+    fn read_directory(
+        &self,
+        path: &Path,
+    ) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        self.fallback.read_directory(path)
+    }
+
+    fn get_metadata(&self, path: &Path) -> Result<Metadata, AssetIoError> {
+        if self.entry_for(path).is_some() {
+            Ok(Metadata::new(bevy::asset::FileType::File))
+        } else {
+            self.fallback.get_metadata(path)
+        }
+    }
+
+    fn watch_path_for_changes(&self, path: &Path) -> Result<(), AssetIoError> {
+        self.fallback.watch_path_for_changes(path)
+    }
+
+    fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+        self.fallback.watch_for_changes()
+    }
+}
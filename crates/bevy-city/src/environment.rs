@@ -0,0 +1,89 @@
+use bevy::{
+    core_pipeline::bloom::BloomSettings,
+    pbr::{CascadeShadowConfigBuilder, DirectionalLightShadowMap},
+    prelude::*,
+};
+
+/// Fraction of a full day/night cycle, `0.0` = midnight, `0.5` = noon.
+pub struct TimeOfDay(pub f32);
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        // Mid-morning by default so the first frame isn't spawned in the dark.
+        Self(0.35)
+    }
+}
+
+const SKY_COLOR: Color = Color::rgb(0.47, 0.66, 0.86);
+
+/// Spawns a directional sun with cascaded shadows and a sky-colored clear
+/// color, replacing the single unlit `PointLight` the viewer used to rely on.
+pub struct EnvironmentPlugin;
+
+impl Plugin for EnvironmentPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(SKY_COLOR))
+            .insert_resource(DirectionalLightShadowMap { size: 4096 })
+            .insert_resource(TimeOfDay::default())
+            .add_startup_system(spawn_sun)
+            .add_system(update_sun_angle);
+    }
+}
+
+fn sun_rotation(time_of_day: f32) -> Quat {
+    let angle = std::f32::consts::FRAC_PI_2 - time_of_day * std::f32::consts::TAU;
+    Quat::from_rotation_x(angle)
+}
+
+fn spawn_sun(mut commands: Commands, time_of_day: Res<TimeOfDay>) {
+    commands.spawn_bundle(DirectionalLightBundle {
+        directional_light: DirectionalLight {
+            illuminance: 15_000.0,
+            shadows_enabled: true,
+            ..default()
+        },
+        transform: Transform::from_rotation(sun_rotation(time_of_day.0)),
+        cascade_shadow_config: CascadeShadowConfigBuilder {
+            first_cascade_far_bound: 30.0,
+            maximum_distance: 500.0,
+            ..default()
+        }
+        .into(),
+        ..default()
+    });
+}
+
+fn update_sun_angle(
+    time_of_day: Res<TimeOfDay>,
+    mut suns: Query<&mut Transform, With<DirectionalLight>>,
+) {
+    if !time_of_day.is_changed() {
+        return;
+    }
+
+    for mut transform in &mut suns {
+        transform.rotation = sun_rotation(time_of_day.0);
+    }
+}
+
+/// Inserts the HDR + bloom components the city's emissive/neon materials need
+/// to actually glow, without every call site re-stating the same bundle.
+///
+/// Uses `Camera3dBundle` rather than the older `PerspectiveCameraBundle`:
+/// `BloomSettings` and `CascadeShadowConfigBuilder` (see `spawn_sun` above)
+/// only exist in Bevy releases that already replaced the perspective/ortho
+/// camera bundles with `Camera3dBundle`, so mixing the two would reference a
+/// Bevy version that never existed.
+pub fn hdr_camera_bundle(transform: Transform) -> (Camera3dBundle, BloomSettings) {
+    (
+        Camera3dBundle {
+            camera: Camera {
+                hdr: true,
+                ..default()
+            },
+            transform,
+            ..default()
+        },
+        BloomSettings::default(),
+    )
+}
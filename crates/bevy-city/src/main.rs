@@ -9,6 +9,23 @@ use clap::Parser;
 pub mod maps;
 use maps::ipl_parser::Ipl;
 
+mod environment;
+use environment::{hdr_camera_bundle, EnvironmentPlugin};
+
+mod streaming;
+use streaming::StreamingPlugin;
+
+mod physics;
+use bevy_xpbd_3d::prelude::{Collider, RigidBody};
+use physics::PhysicsPlugin;
+
+mod assets {
+    pub mod ide;
+    pub mod img_archive;
+}
+use assets::ide::IdePlugin;
+use assets::img_archive::ImgArchiveIo;
+
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
 struct Cli {
@@ -24,11 +41,38 @@ fn main() -> anyhow::Result<()> {
     let args = Cli::parse();
 
     let mut app = App::new();
+
+    // `DefaultPlugins`' `TaskPoolPlugin` is what normally initializes the
+    // `IoTaskPool` singleton, but the `AssetServer` below needs it before
+    // `DefaultPlugins` is added. Initializing it here is safe either way:
+    // `IoTaskPool::init` only builds the pool the first time it's called, so
+    // `TaskPoolPlugin` just reuses this one instead of building its own.
+    bevy::tasks::IoTaskPool::init(|| bevy::tasks::TaskPoolBuilder::default().build());
+
+    // Mount `models/gta3.img` as a virtual filesystem behind the asset
+    // server, so `asset_server.load("foo.dff")` works whether `foo.dff` was
+    // extracted to disk or is still packed inside the archive. Most trees
+    // (including the single-file `asset_viewer` path) won't have the
+    // archive extracted at all, so fall back to the stock file-backed IO
+    // rather than leaving the app without an `AssetServer`.
+    let io: Box<dyn bevy::asset::AssetIo> =
+        match ImgArchiveIo::new("assets/models/gta3.dir", bevy::asset::create_platform_default_asset_io(&mut app)) {
+            Ok(img_io) => Box::new(img_io),
+            Err(_) => bevy::asset::create_platform_default_asset_io(&mut app),
+        };
+    app.insert_resource(AssetServer::new(io, bevy::tasks::IoTaskPool::get().clone()));
+
     app.insert_resource(Msaa { samples: 4 })
-        .add_plugins(DefaultPlugins)
+        .add_plugins_with(DefaultPlugins, |group| {
+            group.disable::<bevy::asset::AssetPlugin>()
+        })
         .add_plugin(bevy_renderware::RwPlugin)
         .add_plugin(maps::ipl_parser::IplPlugin)
+        .add_plugin(IdePlugin)
         .add_plugin(EditorPlugin)
+        .add_plugin(EnvironmentPlugin)
+        .add_plugin(StreamingPlugin)
+        .add_plugin(PhysicsPlugin)
         .insert_resource(DesiredAssetMeshes(vec![]));
 
     if let Some(path) = args.path {
@@ -38,9 +82,7 @@ fn main() -> anyhow::Result<()> {
         app.add_startup_system(load_maps);
     };
 
-    app.add_system(process_pending_desired_meshes)
-        .add_system(handle_ipl_events)
-        .run();
+    app.add_system(process_pending_desired_meshes).run();
 
     Ok(())
 }
@@ -57,21 +99,9 @@ fn asset_viewer(
         false,
     ));
 
-    commands.spawn_bundle(PointLightBundle {
-        point_light: PointLight {
-            intensity: 1500.0,
-            shadows_enabled: true,
-            ..default()
-        },
-        transform: Transform::from_xyz(4.0, 8.0, 4.0),
-        ..default()
-    });
-
-    commands.spawn_bundle(PerspectiveCameraBundle {
-        transform: Transform::from_xyz(-1.0, 1.0, -1.0)
-            .looking_at(Vec3::new(0.0, 0.5, 0.0), Vec3::Y),
-        ..default()
-    });
+    commands.spawn_bundle(hdr_camera_bundle(
+        Transform::from_xyz(-1.0, 1.0, -1.0).looking_at(Vec3::new(0.0, 0.5, 0.0), Vec3::Y),
+    ));
 }
 
 fn process_pending_desired_meshes(
@@ -96,18 +126,28 @@ fn process_pending_desired_meshes(
                 .map(|m| Color::rgba_u8(m.color.r, m.color.g, m.color.b, m.color.a))
                 .unwrap_or(Color::WHITE);
             let base_color_texture = asset_path.map(|ap| asset_server.load(ap.clone()));
+            // Only materials the DFF itself flags as unlit (e.g. baked light
+            // maps) skip the cascaded sun shadow; everything else is lit.
+            let unlit = dff_material.map(|m| m.flags.unlit).unwrap_or(false);
 
-            commands.spawn_bundle(PbrBundle {
+            let mut entity_commands = commands.spawn_bundle(PbrBundle {
                 mesh,
                 material: materials.add(StandardMaterial {
                     base_color,
                     base_color_texture,
-                    unlit: true,
+                    unlit,
                     ..default()
                 }),
                 transform: transform.clone(),
                 ..default()
             });
+
+            // The same triangle/vertex data the mesh was built from also
+            // doubles as a static trimesh collider, so the geometry is walkable.
+            if let Some(collider) = Collider::trimesh_from_mesh(&dff.mesh) {
+                entity_commands.insert(RigidBody::Static).insert(collider);
+            }
+
             *spawned = true;
         }
     }
@@ -155,42 +195,17 @@ fn load_maps(mut commands: Commands, asset_server: Res<AssetServer>) {
             .collect::<Vec<Handle<Ipl>>>(),
     );
 
-    commands.spawn_bundle(PerspectiveCameraBundle {
-        transform: Transform::from_translation(Vec3::ONE * 1000.0).looking_at(Vec3::ZERO, Vec3::Y),
-        ..default()
-    });
-}
+    // `.ide`s sit next to their `.ipl` twin and feed the IdeRegistry that
+    // streaming reads draw distance from.
+    commands.insert_resource(
+        MAP_PATHS
+            .iter()
+            .map(|path| asset_server.load(&path.replace(".ipl", ".ide")) as Handle<assets::ide::Ide>)
+            .collect::<Vec<Handle<assets::ide::Ide>>>(),
+    );
 
-fn handle_ipl_events(
-    mut ev_asset: EventReader<AssetEvent<Ipl>>,
-    mut desired_asset_meshes: ResMut<DesiredAssetMeshes>,
-    asset_server: Res<AssetServer>,
-    assets: Res<Assets<Ipl>>,
-) {
-    for ev in ev_asset.iter() {
-        match ev {
-            AssetEvent::Created { handle } => {
-                let ipl = assets.get(handle).unwrap();
-
-                for (name, [x, y, z]) in &ipl.instances {
-                    if name.len() > 3 && name[..3].eq_ignore_ascii_case("lod") {
-                        continue;
-                    }
-
-                    let path = format!("models/gta3/{name}.dff");
-                    let model_handle = asset_server.load(&path);
-
-                    desired_asset_meshes.0.push((
-                        model_handle,
-                        Transform::from_xyz(*x, *y, *z),
-                        false,
-                    ));
-                }
-            }
-            AssetEvent::Modified { handle: _handle } => {
-                panic!("you aren't meant to modify the IPLs during gameplay!");
-            }
-            AssetEvent::Removed { handle: _handle } => {}
-        }
-    }
+    commands.spawn_bundle(hdr_camera_bundle(
+        Transform::from_translation(Vec3::ONE * 1000.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
 }
+
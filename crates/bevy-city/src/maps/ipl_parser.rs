@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+
+use bevy::{
+    app::prelude::*,
+    asset::{AddAsset, AssetLoader, LoadedAsset},
+    math::{Quat, Vec3},
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use nom::{multi::count, number::complete as nc, sequence::tuple, IResult};
+
+/// Magic bytes at the start of the packed binary IPL layout introduced for
+/// San Andreas; text IPLs (III, VC) start with a comment or an `inst` line.
+const BINARY_MAGIC: &[u8; 4] = b"bnry";
+
+#[derive(Debug, PartialEq)]
+pub struct Instance {
+    pub model_name: String,
+    pub interior: u32,
+    pub position: Vec3,
+    pub scale: Vec3,
+    pub rotation: Quat,
+}
+
+#[derive(Debug, TypeUuid, PartialEq)]
+#[uuid = "eef31d55-f995-4073-87a0-3c50e7fabef7"]
+pub struct Ipl {
+    pub instances: Vec<Instance>,
+}
+
+impl Ipl {
+    pub fn parse(data: &str) -> Self {
+        let sections = categorise_lines(data);
+
+        let instances: Vec<_> = sections
+            .get("inst")
+            .expect("no inst")
+            .iter()
+            .map(|line| {
+                let segments: Vec<_> = split_line(line);
+                let parse_vec3 = |p: &[&str], flip: bool| {
+                    let flip = if flip { -1.0 } else { 1.0 };
+                    Vec3::new(
+                        p[0].parse().unwrap(),
+                        p[2].parse().unwrap(),
+                        p[1].parse::<f32>().unwrap() * flip,
+                    )
+                };
+
+                let quat = &segments[9..];
+                let rotation = flip_rotation_axes(Quat::from_xyzw(
+                    quat[0].parse().unwrap(),
+                    quat[1].parse().unwrap(),
+                    quat[2].parse().unwrap(),
+                    quat[3].parse().unwrap(),
+                ));
+
+                Instance {
+                    model_name: segments[1].to_string(),
+                    interior: segments[2].parse().unwrap(),
+                    position: parse_vec3(&segments[3..6], true),
+                    scale: parse_vec3(&segments[6..9], false),
+                    rotation,
+                }
+            })
+            .collect();
+
+        Ipl { instances }
+    }
+
+    /// Parses the packed binary `bnry` layout later titles use instead of
+    /// the text `inst` section, picking the right entry point by sniffing
+    /// the file's magic bytes. Returns an error instead of panicking on
+    /// truncated/corrupt input so a single bad file can't take down the
+    /// asset-loading task.
+    pub fn parse_bytes(data: &[u8]) -> anyhow::Result<Self> {
+        if data.starts_with(BINARY_MAGIC) {
+            Self::parse_binary(data)
+                .map(|(_, ipl)| ipl)
+                .map_err(|err| anyhow::anyhow!("malformed binary IPL: {err:?}"))
+        } else {
+            Ok(Self::parse(std::str::from_utf8(data)?))
+        }
+    }
+
+    /// `bnry` header: magic, instance count, instance section byte offset.
+    /// Each instance record is a fixed-size struct of model name, interior,
+    /// position, scale and rotation, in that order.
+    fn parse_binary(input: &[u8]) -> IResult<&[u8], Self> {
+        let full_file = input;
+        let (header, _magic) = nom::bytes::complete::tag(BINARY_MAGIC.as_slice())(input)?;
+        let (_, (instance_count, instance_offset)) = tuple((nc::le_u32, nc::le_u32))(header)?;
+        let (_, instances) = count(Self::parse_binary_instance, instance_count as usize)(
+            &full_file[instance_offset as usize..],
+        )?;
+
+        Ok((&[], Ipl { instances }))
+    }
+
+    fn parse_binary_instance(input: &[u8]) -> IResult<&[u8], Instance> {
+        let (input, model_name_bytes) = nom::bytes::complete::take(24usize)(input)?;
+        let (input, interior) = nc::le_u32(input)?;
+        let (input, (x, y, z)) = tuple((nc::le_f32, nc::le_f32, nc::le_f32))(input)?;
+        let (input, (sx, sy, sz)) = tuple((nc::le_f32, nc::le_f32, nc::le_f32))(input)?;
+        let (input, (qx, qy, qz, qw)) =
+            tuple((nc::le_f32, nc::le_f32, nc::le_f32, nc::le_f32))(input)?;
+
+        let model_name = String::from_utf8_lossy(model_name_bytes)
+            .trim_end_matches('\0')
+            .to_string();
+
+        Ok((
+            input,
+            Instance {
+                model_name,
+                interior,
+                position: Vec3::new(x, z, -y),
+                scale: Vec3::new(sx, sz, sy),
+                rotation: flip_rotation_axes(Quat::from_xyzw(qx, qy, qz, qw)),
+            },
+        ))
+    }
+}
+
+/// Applies the same Z-up -> Y-up flip to a rotation that `position` already
+/// gets via `parse_vec3`'s `(x, z, -y)` swap, so spawned orientations agree
+/// with the flipped positions/scales instead of staying in the source file's
+/// coordinate frame. Swapping the quaternion's axis components the same way
+/// equals conjugating by the basis-change rotation (-90 degrees about X,
+/// i.e. `y' = z, z' = -y`), without the float rounding a quaternion product
+/// would add (an identity rotation stays exactly identity).
+fn flip_rotation_axes(rotation: Quat) -> Quat {
+    Quat::from_xyzw(rotation.x, rotation.z, -rotation.y, rotation.w)
+}
+
+/// Groups an IPL's comma-separated-value lines by section name (`inst`,
+/// `cull`, `pick`, `path`, ...), dropping comment lines and the `end`
+/// terminator each section is closed with.
+pub(crate) fn categorise_lines(data: &str) -> HashMap<&str, Vec<&str>> {
+    let mut sections: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut current_section: Option<&str> = None;
+
+    for line in data.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        match current_section {
+            Some(_) if line == "end" => current_section = None,
+            Some(section) => sections.entry(section).or_default().push(line),
+            None => {
+                current_section = Some(line);
+                sections.entry(line).or_default();
+            }
+        }
+    }
+
+    sections
+}
+
+/// Splits one CSV-style IPL row into its trimmed, comma-separated fields.
+pub(crate) fn split_line(line: &str) -> Vec<&str> {
+    line.split(',').map(str::trim).collect()
+}
+
+#[derive(Default)]
+pub struct IplLoader;
+
+impl AssetLoader for IplLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut bevy::asset::LoadContext,
+    ) -> BoxedFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let value = Ipl::parse_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(value));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        static EXTENSIONS: &[&str] = &["ipl"];
+        EXTENSIONS
+    }
+}
+
+/// Adds support for Ipl file loading to Apps
+#[derive(Default)]
+pub struct IplPlugin;
+impl Plugin for IplPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Ipl>().init_asset_loader::<IplLoader>();
+    }
+}
+
+mod tests {
+    pub use super::*;
+
+    #[test]
+    fn can_parse_downtown_subset() {
+        const TEST_DATA: &str = r"
+# IPL generated from Max file downtown.max
+inst
+1860, doontoon03, 0, -445.4862671, 1280.132813, 42.78390503, 1, 1, 1, 0, 0, 0, 1
+1861, doontoon04, 0, -303.8299866, 1394.506836, 6.610000134, 1, 1, 1, 0, 0, 0, 1
+1862, doontoon09, 0, -798.4454346, 1039.305176, 12.29159546, 1, 1, 1, 0, 0, 0, 1
+end
+cull
+end
+pick
+end
+path
+end
+";
+
+        let test_data = TEST_DATA.trim();
+        assert_eq!(
+            Ipl::parse(test_data),
+            Ipl {
+                instances: vec![
+                    Instance {
+                        model_name: "doontoon03".to_string(),
+                        interior: 0,
+                        position: Vec3::new(-445.48627, 42.783905, -1280.1328),
+                        scale: Vec3::new(1.0, 1.0, 1.0),
+                        rotation: Quat::from_xyzw(0.0, 0.0, 0.0, 1.0),
+                    },
+                    Instance {
+                        model_name: "doontoon04".to_string(),
+                        interior: 0,
+                        position: Vec3::new(-303.83, 6.61, -1394.5068),
+                        scale: Vec3::new(1.0, 1.0, 1.0),
+                        rotation: Quat::from_xyzw(0.0, 0.0, 0.0, 1.0),
+                    },
+                    Instance {
+                        model_name: "doontoon09".to_string(),
+                        interior: 0,
+                        position: Vec3::new(-798.44543, 12.291595, -1039.3052),
+                        scale: Vec3::new(1.0, 1.0, 1.0),
+                        rotation: Quat::from_xyzw(0.0, 0.0, 0.0, 1.0),
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn parse_bytes_dispatches_on_bnry_magic() {
+        let mut data = b"bnry".to_vec();
+        data.extend_from_slice(&1u32.to_le_bytes()); // instance_count
+        data.extend_from_slice(&12u32.to_le_bytes()); // instance_offset
+
+        let mut name = [0u8; 24];
+        name[..3].copy_from_slice(b"foo");
+        data.extend_from_slice(&name);
+        data.extend_from_slice(&0u32.to_le_bytes()); // interior
+        data.extend_from_slice(&1.0f32.to_le_bytes()); // x
+        data.extend_from_slice(&2.0f32.to_le_bytes()); // y
+        data.extend_from_slice(&3.0f32.to_le_bytes()); // z
+        data.extend_from_slice(&1.0f32.to_le_bytes()); // sx
+        data.extend_from_slice(&1.0f32.to_le_bytes()); // sy
+        data.extend_from_slice(&1.0f32.to_le_bytes()); // sz
+        data.extend_from_slice(&0.0f32.to_le_bytes()); // qx
+        data.extend_from_slice(&0.0f32.to_le_bytes()); // qy
+        data.extend_from_slice(&0.0f32.to_le_bytes()); // qz
+        data.extend_from_slice(&1.0f32.to_le_bytes()); // qw
+
+        let ipl = Ipl::parse_bytes(&data).unwrap();
+        assert_eq!(ipl.instances.len(), 1);
+        assert_eq!(ipl.instances[0].model_name, "foo");
+        assert_eq!(ipl.instances[0].position, Vec3::new(1.0, 3.0, -2.0));
+    }
+
+    #[test]
+    fn parse_bytes_reports_error_on_truncated_binary() {
+        let data = b"bnry".to_vec();
+        assert!(Ipl::parse_bytes(&data).is_err());
+    }
+
+    #[test]
+    fn parse_applies_axis_flip_to_non_identity_rotation() {
+        // A 90-degree yaw (rotation about the source file's up axis) should
+        // come out as a 90-degree rotation about Bevy's Y (the axis the
+        // source file's up axis was flipped onto), not pass through as-is.
+        const TEST_DATA: &str = r"
+inst
+1, foo, 0, 0, 0, 0, 1, 1, 1, 0, 0, 0.7071068, 0.7071068
+end
+cull
+end
+pick
+end
+path
+end
+";
+        let ipl = Ipl::parse(TEST_DATA.trim());
+        let expected = flip_rotation_axes(Quat::from_xyzw(0.0, 0.0, 0.7071068, 0.7071068));
+        assert_ne!(ipl.instances[0].rotation, Quat::from_xyzw(0.0, 0.0, 0.7071068, 0.7071068));
+        assert!((ipl.instances[0].rotation.x - expected.x).abs() < 1e-5);
+        assert!((ipl.instances[0].rotation.y - expected.y).abs() < 1e-5);
+        assert!((ipl.instances[0].rotation.z - expected.z).abs() < 1e-5);
+        assert!((ipl.instances[0].rotation.w - expected.w).abs() < 1e-5);
+    }
+}
@@ -0,0 +1 @@
+pub mod ipl_parser;
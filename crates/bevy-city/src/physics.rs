@@ -0,0 +1,161 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+const WALK_SPEED: f32 = 4.5;
+const FLY_SPEED: f32 = 12.0;
+const JUMP_VELOCITY: f32 = 5.5;
+/// How far below the player's centre (its transform, not its feet) to look
+/// for ground before allowing a jump.
+const GROUNDED_CHECK_DISTANCE: f32 = 1.1;
+
+#[derive(Component)]
+pub struct Player;
+
+/// Whether the player entity is gravity-bound and ground-clamped, or flies
+/// freely as a gravity-less kinematic body moved directly by input. The
+/// player entity carries no camera; the scene camera is spawned separately.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ControllerMode {
+    Fly,
+    Walk,
+}
+
+impl Default for ControllerMode {
+    fn default() -> Self {
+        // Flying is the more useful default while browsing a freshly loaded map.
+        Self::Fly
+    }
+}
+
+/// Adds rigid-body physics, static trimesh colliders for spawned `Dff`
+/// meshes (see `process_pending_desired_meshes`), and a player controller
+/// that toggles between grounded walking and free-fly.
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(XpbdPlugin::default())
+            .insert_resource(Gravity(Vec3::NEG_Y * 9.81))
+            .insert_resource(ControllerMode::default())
+            .add_startup_system(spawn_player)
+            .add_system(toggle_controller_mode)
+            .add_system(grounded_controller)
+            .add_system(fly_controller);
+    }
+}
+
+/// The player's `RigidBody` for a given controller mode. Walking needs a
+/// `Dynamic` body so xpbd's solver can apply gravity/velocity and collide
+/// with the world; flying drives `Transform` directly every frame, which
+/// would otherwise fight a `Dynamic` body's solver-owned position, so fly
+/// mode switches to `Kinematic` instead.
+fn rigid_body_for(mode: ControllerMode) -> RigidBody {
+    match mode {
+        ControllerMode::Fly => RigidBody::Kinematic,
+        ControllerMode::Walk => RigidBody::Dynamic,
+    }
+}
+
+fn spawn_player(mut commands: Commands) {
+    commands
+        .spawn_bundle(TransformBundle::from_transform(Transform::from_xyz(
+            0.0, 2.0, 0.0,
+        )))
+        .insert(Player)
+        .insert(rigid_body_for(ControllerMode::default()))
+        .insert(Collider::capsule(1.0, 0.4))
+        .insert(LockedAxes::ROTATION_LOCKED)
+        .insert(GravityScale(0.0));
+}
+
+fn toggle_controller_mode(
+    keys: Res<Input<KeyCode>>,
+    mut mode: ResMut<ControllerMode>,
+    mut players: Query<(Entity, &mut GravityScale), With<Player>>,
+    mut commands: Commands,
+) {
+    if !keys.just_pressed(KeyCode::F) {
+        return;
+    }
+
+    *mode = match *mode {
+        ControllerMode::Fly => ControllerMode::Walk,
+        ControllerMode::Walk => ControllerMode::Fly,
+    };
+
+    for (entity, mut gravity_scale) in &mut players {
+        gravity_scale.0 = if *mode == ControllerMode::Walk { 1.0 } else { 0.0 };
+        commands.entity(entity).insert(rigid_body_for(*mode));
+    }
+}
+
+fn grounded_controller(
+    mode: Res<ControllerMode>,
+    keys: Res<Input<KeyCode>>,
+    spatial_query: SpatialQuery,
+    mut players: Query<(Entity, &mut LinearVelocity, &Transform), With<Player>>,
+) {
+    if *mode != ControllerMode::Walk {
+        return;
+    }
+
+    for (entity, mut velocity, transform) in &mut players {
+        let movement = movement_direction(&keys, transform);
+        velocity.x = movement.x * WALK_SPEED;
+        velocity.z = movement.z * WALK_SPEED;
+
+        let grounded = spatial_query
+            .cast_ray(
+                transform.translation,
+                Vec3::NEG_Y,
+                GROUNDED_CHECK_DISTANCE,
+                true,
+                SpatialQueryFilter::default().without_entities([entity]),
+            )
+            .is_some();
+
+        if grounded && keys.just_pressed(KeyCode::Space) {
+            velocity.y = JUMP_VELOCITY;
+        }
+    }
+}
+
+fn fly_controller(
+    mode: Res<ControllerMode>,
+    keys: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    mut players: Query<&mut Transform, With<Player>>,
+) {
+    if *mode != ControllerMode::Fly {
+        return;
+    }
+
+    for mut transform in &mut players {
+        let mut movement = movement_direction(&keys, &transform);
+        if keys.pressed(KeyCode::Space) {
+            movement += Vec3::Y;
+        }
+        if keys.pressed(KeyCode::LShift) {
+            movement -= Vec3::Y;
+        }
+
+        transform.translation += movement.normalize_or_zero() * FLY_SPEED * time.delta_seconds();
+    }
+}
+
+fn movement_direction(keys: &Input<KeyCode>, transform: &Transform) -> Vec3 {
+    let mut direction = Vec3::ZERO;
+    if keys.pressed(KeyCode::W) {
+        direction += transform.forward();
+    }
+    if keys.pressed(KeyCode::S) {
+        direction -= transform.forward();
+    }
+    if keys.pressed(KeyCode::A) {
+        direction -= transform.right();
+    }
+    if keys.pressed(KeyCode::D) {
+        direction += transform.right();
+    }
+    direction.normalize_or_zero()
+}
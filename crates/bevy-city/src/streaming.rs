@@ -0,0 +1,470 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::{Collider, RigidBody};
+
+use bevy_renderware::dff::Dff;
+
+use crate::assets::ide::IdeRegistry;
+use crate::maps::ipl_parser::Ipl;
+
+/// Size, in world units, of one spatial-grid cell. Tuned for a city-scale
+/// map: big enough that a handful of cells cover the camera's draw radius.
+const GRID_CELL_SIZE: f32 = 100.0;
+
+#[derive(Clone)]
+struct GridInstance {
+    model_name: String,
+    lod_model_name: Option<String>,
+    position: Vec3,
+    rotation: Quat,
+    scale: Vec3,
+    /// 0 is the exterior; anything else ties the instance to a specific
+    /// interior so it only streams in while that interior is selected.
+    interior: u32,
+}
+
+impl GridInstance {
+    fn transform(&self) -> Transform {
+        Transform {
+            translation: self.position,
+            rotation: self.rotation,
+            scale: self.scale,
+        }
+    }
+}
+
+/// Which interior's instances are currently eligible to stream in. `0`
+/// (the default) is the exterior world; any other value hides everything
+/// except instances placed in that interior.
+pub struct InteriorFilter(pub u32);
+
+impl Default for InteriorFilter {
+    fn default() -> Self {
+        Self(0)
+    }
+}
+
+/// Every IPL `Instance` bucketed by XY cell, keyed independently of spawn
+/// state so streaming can be re-evaluated every frame without re-parsing.
+#[derive(Default)]
+pub struct InstanceGrid {
+    instances: Vec<GridInstance>,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl InstanceGrid {
+    fn cell_of(position: Vec3) -> (i32, i32) {
+        (
+            (position.x / GRID_CELL_SIZE).floor() as i32,
+            (position.z / GRID_CELL_SIZE).floor() as i32,
+        )
+    }
+
+    fn insert(&mut self, instance: GridInstance) {
+        let cell = Self::cell_of(instance.position);
+        let id = self.instances.len();
+        self.instances.push(instance);
+        self.cells.entry(cell).or_default().push(id);
+    }
+
+    fn ids_within(&self, center: Vec3, radius: f32) -> impl Iterator<Item = usize> + '_ {
+        let (cx, cz) = Self::cell_of(center);
+        let span = (radius / GRID_CELL_SIZE).ceil() as i32;
+        (-span..=span)
+            .flat_map(move |dx| (-span..=span).map(move |dz| (cx + dx, cz + dz)))
+            .filter_map(move |cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+    }
+}
+
+/// Near/far streaming radii, in world units, with a hysteresis band so
+/// objects sitting right on a boundary don't swap detail level every frame.
+pub struct StreamingConfig {
+    pub hi_detail_radius: f32,
+    pub lod_radius: f32,
+    pub hysteresis: f32,
+}
+
+impl Default for StreamingConfig {
+    fn default() -> Self {
+        Self {
+            hi_detail_radius: 150.0,
+            lod_radius: 400.0,
+            hysteresis: 20.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Detail {
+    Hi,
+    Lod,
+}
+
+struct StreamedInstance {
+    entity: Entity,
+    detail: Detail,
+    dff_handle: Handle<Dff>,
+    /// `asset_server.load` returns before the `Dff` has actually decoded, so
+    /// the entity is first spawned with a placeholder mesh and no collider.
+    /// Mirrors the `spawned` flag `process_pending_desired_meshes` uses for
+    /// the same reason; stays `false` until the real mesh has been swapped in.
+    mesh_loaded: bool,
+}
+
+#[derive(Default)]
+pub struct StreamedInstances(HashMap<usize, StreamedInstance>);
+
+pub struct StreamingPlugin;
+
+impl Plugin for StreamingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InstanceGrid>()
+            .init_resource::<StreamedInstances>()
+            .init_resource::<InteriorFilter>()
+            .insert_resource(StreamingConfig::default())
+            .add_system(index_loaded_ipls)
+            .add_system(cycle_interior_filter)
+            .add_system(stream_instances);
+    }
+}
+
+/// Cycles `InteriorFilter` through every interior id present in the grid
+/// (exterior included) on each press of `I`, the same single-key-toggle
+/// input style `physics.rs` uses for its fly/walk switch.
+fn cycle_interior_filter(
+    keys: Res<Input<KeyCode>>,
+    grid: Res<InstanceGrid>,
+    mut interior_filter: ResMut<InteriorFilter>,
+) {
+    if !keys.just_pressed(KeyCode::I) {
+        return;
+    }
+
+    let mut interiors: Vec<u32> = grid.instances.iter().map(|instance| instance.interior).collect();
+    interiors.sort_unstable();
+    interiors.dedup();
+    if interiors.is_empty() {
+        return;
+    }
+
+    let current_index = interiors
+        .iter()
+        .position(|&interior| interior == interior_filter.0)
+        .unwrap_or(0);
+    interior_filter.0 = interiors[(current_index + 1) % interiors.len()];
+}
+
+/// Buckets newly-loaded IPLs into the grid instead of spawning their
+/// instances immediately, pairing each hi-detail instance with its `lod*`
+/// counterpart by name so the two can be swapped later.
+fn index_loaded_ipls(
+    mut ev_asset: EventReader<AssetEvent<Ipl>>,
+    assets: Res<Assets<Ipl>>,
+    mut grid: ResMut<InstanceGrid>,
+) {
+    for ev in ev_asset.iter() {
+        let AssetEvent::Created { handle } = ev else {
+            continue;
+        };
+        let ipl = assets.get(handle).unwrap();
+
+        let mut lod_by_base_name: HashMap<String, &str> = HashMap::new();
+        for instance in &ipl.instances {
+            if is_lod_name(&instance.model_name) {
+                lod_by_base_name.insert(
+                    instance.model_name[3..].to_lowercase(),
+                    &instance.model_name,
+                );
+            }
+        }
+
+        for instance in &ipl.instances {
+            if is_lod_name(&instance.model_name) {
+                continue;
+            }
+
+            grid.insert(GridInstance {
+                lod_model_name: lod_by_base_name
+                    .get(&instance.model_name.to_lowercase())
+                    .map(|name| name.to_string()),
+                model_name: instance.model_name.clone(),
+                position: instance.position,
+                rotation: instance.rotation,
+                scale: instance.scale,
+                interior: instance.interior,
+            });
+        }
+    }
+}
+
+fn is_lod_name(model_name: &str) -> bool {
+    model_name.len() > 3 && model_name[..3].eq_ignore_ascii_case("lod")
+}
+
+/// Builds the same base color/texture/`unlit` material `process_pending_desired_meshes`
+/// derives from a loaded `Dff`, so streamed instances don't render flat white.
+fn build_material(dff: &Dff, asset_server: &AssetServer) -> StandardMaterial {
+    let dff_material = dff.materials.get(0);
+    let asset_path = dff.asset_paths.get(0);
+
+    let base_color = dff_material
+        .map(|m| Color::rgba_u8(m.color.r, m.color.g, m.color.b, m.color.a))
+        .unwrap_or(Color::WHITE);
+    let base_color_texture = asset_path.map(|ap| asset_server.load(ap.clone()));
+    // Only materials the DFF itself flags as unlit (e.g. baked light maps)
+    // skip the cascaded sun shadow; everything else is lit.
+    let unlit = dff_material.map(|m| m.flags.unlit).unwrap_or(false);
+
+    StandardMaterial {
+        base_color,
+        base_color_texture,
+        unlit,
+        ..default()
+    }
+}
+
+/// Each frame, spawns the hi-detail model for instances close to the camera,
+/// the paired `lod*` model for the outer ring, and despawns anything that has
+/// fallen outside the far ring entirely.
+fn stream_instances(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<StreamingConfig>,
+    ide_registry: Res<IdeRegistry>,
+    interior_filter: Res<InteriorFilter>,
+    grid: Res<InstanceGrid>,
+    mut streamed: ResMut<StreamedInstances>,
+    cameras: Query<&Transform, With<Camera>>,
+    dff_assets: Res<Assets<Dff>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let Some(camera_transform) = cameras.iter().next() else {
+        return;
+    };
+    let camera_pos = camera_transform.translation;
+
+    let mut seen = HashSet::new();
+    for id in grid.ids_within(camera_pos, config.lod_radius + config.hysteresis) {
+        let instance = &grid.instances[id];
+        if instance.interior != interior_filter.0 {
+            continue;
+        }
+
+        let distance = instance.position.distance(camera_pos);
+        // The .ide's own draw distance for this object wins over the
+        // generic config fallback, same as the game would use it.
+        let lod_radius = ide_registry
+            .get(&instance.model_name)
+            .map(|object| object.draw_distance)
+            .unwrap_or(config.lod_radius);
+        let current = streamed.0.get(&id).map(|s| s.detail);
+        let desired = desired_detail(&config, lod_radius, instance, distance, current);
+        seen.insert(id);
+
+        if desired == current {
+            // Detail level hasn't changed, but the mesh may still be
+            // in-flight from when this instance first streamed in — keep
+            // polling until it resolves instead of leaving it an empty
+            // placeholder forever.
+            if let Some(streamed_instance) = streamed.0.get_mut(&id) {
+                if !streamed_instance.mesh_loaded {
+                    if let Some(dff) = dff_assets.get(&streamed_instance.dff_handle) {
+                        commands
+                            .entity(streamed_instance.entity)
+                            .insert(meshes.add(dff.mesh.clone()))
+                            .insert(materials.add(build_material(dff, &asset_server)));
+                        if streamed_instance.detail == Detail::Hi {
+                            if let Some(collider) = Collider::trimesh_from_mesh(&dff.mesh) {
+                                commands
+                                    .entity(streamed_instance.entity)
+                                    .insert(RigidBody::Static)
+                                    .insert(collider);
+                            }
+                        }
+                        streamed_instance.mesh_loaded = true;
+                    }
+                }
+            }
+            continue;
+        }
+
+        if let Some(streamed_instance) = streamed.0.remove(&id) {
+            commands.entity(streamed_instance.entity).despawn_recursive();
+        }
+
+        let Some(detail) = desired else {
+            continue;
+        };
+
+        let model_name = match detail {
+            Detail::Hi => instance.model_name.as_str(),
+            // Objects without a paired LOD just stay hi-detail in the outer ring.
+            Detail::Lod => instance
+                .lod_model_name
+                .as_deref()
+                .unwrap_or(instance.model_name.as_str()),
+        };
+
+        // The mounted .img archive resolves a bare model name on its own, so
+        // spawning no longer assumes a `models/gta3/` directory convention.
+        let dff_handle: Handle<Dff> = asset_server.load(&format!("{model_name}.dff"));
+        let dff = dff_assets.get(&dff_handle);
+        let mesh = dff.map(|dff| meshes.add(dff.mesh.clone())).unwrap_or_default();
+        let material = dff
+            .map(|dff| materials.add(build_material(dff, &asset_server)))
+            .unwrap_or_default();
+
+        let mut entity_commands = commands.spawn_bundle(PbrBundle {
+            mesh,
+            material,
+            transform: instance.transform(),
+            ..default()
+        });
+
+        if detail == Detail::Hi {
+            if let Some(collider) = dff.and_then(|dff| Collider::trimesh_from_mesh(&dff.mesh)) {
+                entity_commands.insert(RigidBody::Static).insert(collider);
+            }
+        }
+
+        streamed.0.insert(
+            id,
+            StreamedInstance {
+                entity: entity_commands.id(),
+                detail,
+                dff_handle,
+                mesh_loaded: dff.is_some(),
+            },
+        );
+    }
+
+    streamed.0.retain(|id, streamed_instance| {
+        if seen.contains(id) {
+            true
+        } else {
+            commands.entity(streamed_instance.entity).despawn_recursive();
+            false
+        }
+    });
+}
+
+fn desired_detail(
+    config: &StreamingConfig,
+    lod_radius: f32,
+    instance: &GridInstance,
+    distance: f32,
+    current: Option<Detail>,
+) -> Option<Detail> {
+    let has_lod = instance.lod_model_name.is_some();
+
+    match current {
+        Some(Detail::Hi) => {
+            if distance <= config.hi_detail_radius + config.hysteresis {
+                Some(Detail::Hi)
+            } else if !has_lod || distance <= lod_radius + config.hysteresis {
+                Some(Detail::Lod)
+            } else {
+                None
+            }
+        }
+        Some(Detail::Lod) => {
+            if distance <= config.hi_detail_radius {
+                Some(Detail::Hi)
+            } else if distance <= lod_radius + config.hysteresis {
+                Some(Detail::Lod)
+            } else {
+                None
+            }
+        }
+        None => {
+            if distance <= config.hi_detail_radius {
+                Some(Detail::Hi)
+            } else if distance <= lod_radius {
+                Some(Detail::Lod)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+mod tests {
+    pub use super::*;
+
+    #[test]
+    fn grid_instance_transform_honours_rotation_and_scale() {
+        // `GridInstance::rotation` is expected to already be in Bevy's Y-up
+        // frame by the time it reaches here — `ipl_parser.rs`'s `flip_rotation_axes`
+        // applies the same axis flip used for position/scale when the
+        // `Instance` is first parsed, so this only has to check pass-through.
+        let instance = GridInstance {
+            model_name: "foo".to_string(),
+            lod_model_name: None,
+            position: Vec3::new(1.0, 2.0, 3.0),
+            rotation: Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+            scale: Vec3::splat(2.0),
+            interior: 0,
+        };
+
+        let transform = instance.transform();
+        assert_eq!(transform.translation, instance.position);
+        assert_eq!(transform.rotation, instance.rotation);
+        assert_eq!(transform.scale, instance.scale);
+    }
+
+    #[test]
+    fn cells_within_covers_neighbouring_cells() {
+        let mut grid = InstanceGrid::default();
+        grid.insert(GridInstance {
+            model_name: "foo".to_string(),
+            lod_model_name: None,
+            position: Vec3::new(5.0, 0.0, 5.0),
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+            interior: 0,
+        });
+        grid.insert(GridInstance {
+            model_name: "bar".to_string(),
+            lod_model_name: None,
+            position: Vec3::new(250.0, 0.0, 5.0),
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+            interior: 0,
+        });
+
+        let nearby: Vec<_> = grid.ids_within(Vec3::ZERO, 50.0).collect();
+        assert_eq!(nearby, vec![0]);
+
+        let far: Vec<_> = grid.ids_within(Vec3::ZERO, 300.0).collect();
+        assert_eq!(far.len(), 2);
+    }
+
+    #[test]
+    fn hi_detail_instance_holds_until_hysteresis_band() {
+        let config = StreamingConfig::default();
+        let instance = GridInstance {
+            model_name: "foo".to_string(),
+            lod_model_name: Some("lodfoo".to_string()),
+            position: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+            interior: 0,
+        };
+
+        let just_past_near = config.hi_detail_radius + config.hysteresis - 1.0;
+        assert_eq!(
+            desired_detail(&config, config.lod_radius, &instance, just_past_near, Some(Detail::Hi)),
+            Some(Detail::Hi)
+        );
+
+        let past_band = config.hi_detail_radius + config.hysteresis + 1.0;
+        assert_eq!(
+            desired_detail(&config, config.lod_radius, &instance, past_band, Some(Detail::Hi)),
+            Some(Detail::Lod)
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use nom::{number::complete as nc, sequence::tuple, IResult};
 
+use crate::version::GameVersion;
+
 #[derive(Debug, PartialEq)]
 pub struct Triangle {
     vertex1: u16,
@@ -8,17 +10,37 @@ pub struct Triangle {
     material_id: u16,
 }
 impl Triangle {
-    pub(crate) fn parse(input: &[u8]) -> IResult<&[u8], Self> {
-        let (input, (vertex2, vertex1, material_id, vertex3)) =
-            tuple((nc::le_u16, nc::le_u16, nc::le_u16, nc::le_u16))(input)?;
-        Ok((
-            input,
-            Triangle {
-                vertex1,
-                vertex2,
-                vertex3,
-                material_id,
-            },
-        ))
+    /// Picks the on-disk triangle layout for `version` before decoding.
+    ///
+    /// The geometry chunk's own `ChunkHeader` (see `version::ChunkHeader`)
+    /// carries the RW library ID this `version` should come from — callers
+    /// read that header once per geometry and thread the decoded
+    /// `GameVersion` through every `Triangle::parse_versioned` call for its
+    /// triangle list. There is deliberately no version-less `parse()`
+    /// fallback: a hardcoded default here previously made every file look
+    /// like GTA III regardless of what it actually was.
+    ///
+    /// Every title RenderWare version we've seen so far (III, VC, SA) packs
+    /// the same four `u16`s in the same order, so there's currently one
+    /// branch doing the actual reading; this exists so a title with a
+    /// genuinely different layout only needs a new match arm here instead
+    /// of a rewrite of every call site.
+    pub(crate) fn parse_versioned(version: GameVersion, input: &[u8]) -> IResult<&[u8], Self> {
+        match version {
+            GameVersion::GtaIII | GameVersion::ViceCity | GameVersion::SanAndreas
+            | GameVersion::Unknown(_) => {
+                let (input, (vertex2, vertex1, material_id, vertex3)) =
+                    tuple((nc::le_u16, nc::le_u16, nc::le_u16, nc::le_u16))(input)?;
+                Ok((
+                    input,
+                    Triangle {
+                        vertex1,
+                        vertex2,
+                        vertex3,
+                        material_id,
+                    },
+                ))
+            }
+        }
     }
 }
@@ -0,0 +1,103 @@
+use nom::{number::complete as nc, sequence::tuple, IResult};
+
+/// Which GTA title's RenderWare/IPL dialect a file was authored for.
+///
+/// Each title bundles a different RenderWare library version and, for IPLs,
+/// a different on-disk layout (text for III/VC, packed binary for SA).
+/// `Triangle::parse_versioned` dispatches on this to pick a layout; IPL
+/// loading does not use it and tells text from binary by the `bnry` magic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameVersion {
+    GtaIII,
+    ViceCity,
+    SanAndreas,
+    /// A RenderWare library ID we don't recognise; parsed with the closest
+    /// known layout (III/VC) rather than failing outright.
+    Unknown(u32),
+}
+
+impl GameVersion {
+    /// Buckets a RenderWare library ID (as packed in a chunk header) into
+    /// the title that shipped it.
+    ///
+    /// Ranges are derived from the library IDs the three titles actually
+    /// ship (`0x0800FFFF`, `0x1003FFFF`, `0x1803FFFF` for III/VC/SA
+    /// respectively), decoded through [`RwVersion::from_library_id`]:
+    /// `0x00032000`, `0x00034003`, `0x00036003`. See
+    /// `version_ranges_match_documented_library_ids` below for the exact
+    /// round-trip.
+    pub fn from_library_id(library_id: u32) -> Self {
+        match RwVersion::from_library_id(library_id).0 {
+            0x0003_0000..=0x0003_2FFF => GameVersion::GtaIII,
+            0x0003_3000..=0x0003_4FFF => GameVersion::ViceCity,
+            0x0003_6000..=0x0003_6FFF => GameVersion::SanAndreas,
+            other => GameVersion::Unknown(other),
+        }
+    }
+}
+
+/// A decoded RenderWare version number, e.g. `0x00034000` for RW 3.4.0.0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RwVersion(pub u32);
+
+impl RwVersion {
+    /// RenderWare packs the stream version and an internal build number
+    /// into a single "library ID" field; this unpacks just the version.
+    pub fn from_library_id(library_id: u32) -> Self {
+        if library_id & 0xFFFF_0000 != 0 {
+            RwVersion(((library_id >> 14 & 0x3_ff00) + 0x30000) | (library_id >> 16 & 0x3f))
+        } else {
+            RwVersion(library_id << 8)
+        }
+    }
+}
+
+/// The 12-byte header in front of every RenderWare stream chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkHeader {
+    pub chunk_type: u32,
+    pub size: u32,
+    pub library_id: u32,
+}
+
+impl ChunkHeader {
+    pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+        let (input, (chunk_type, size, library_id)) =
+            tuple((nc::le_u32, nc::le_u32, nc::le_u32))(input)?;
+        Ok((
+            input,
+            ChunkHeader {
+                chunk_type,
+                size,
+                library_id,
+            },
+        ))
+    }
+
+    pub fn game_version(&self) -> GameVersion {
+        GameVersion::from_library_id(self.library_id)
+    }
+}
+
+mod tests {
+    pub use super::*;
+
+    #[test]
+    fn version_ranges_match_documented_library_ids() {
+        assert_eq!(RwVersion::from_library_id(0x0800_FFFF), RwVersion(0x0003_2000));
+        assert_eq!(RwVersion::from_library_id(0x1003_FFFF), RwVersion(0x0003_4003));
+        assert_eq!(RwVersion::from_library_id(0x1803_FFFF), RwVersion(0x0003_6003));
+
+        assert_eq!(GameVersion::from_library_id(0x0800_FFFF), GameVersion::GtaIII);
+        assert_eq!(GameVersion::from_library_id(0x1003_FFFF), GameVersion::ViceCity);
+        assert_eq!(GameVersion::from_library_id(0x1803_FFFF), GameVersion::SanAndreas);
+    }
+
+    #[test]
+    fn from_library_id_falls_back_to_unknown() {
+        assert_eq!(
+            GameVersion::from_library_id(0xFFFF_FFFF),
+            GameVersion::Unknown(RwVersion::from_library_id(0xFFFF_FFFF).0)
+        );
+    }
+}